@@ -1,24 +1,35 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use tokio::select;
+use tokio::sync::watch;
 
+use crate::config::{Config, ConfigEvent};
 use crate::desk_controller::DeskEvent;
+use crate::event;
 
 pub(crate) async fn interactive_mode(
     set_target_height: tokio::sync::mpsc::Sender<f32>,
+    config: watch::Receiver<Config>,
 ) -> Result<()> {
     loop {
-        let target = inquire::Text::new("Укажите высоту:").prompt()?;
-        let target: f32 = target.parse()?;
+        let input = inquire::Text::new("Укажите высоту:").prompt()?;
+        let target = config
+            .borrow()
+            .resolve_height(&input)
+            .ok_or_else(|| anyhow!("Unknown height or preset: {input}"))?;
         set_target_height.send(target).await?;
     }
 }
 
 pub(crate) async fn logger(
-    mut desk_events: tokio::sync::broadcast::Receiver<DeskEvent>,
+    mut desk_events: event::Reader<DeskEvent>,
+    mut config_events: tokio::sync::broadcast::Receiver<ConfigEvent>,
 ) -> Result<()> {
     loop {
         select! {
-            Ok(event) = desk_events.recv() => {
+            Some(event) = desk_events.recv() => {
+                println!("{:#?}", event);
+            }
+            Ok(event) = config_events.recv() => {
                 println!("{:#?}", event);
             }
         }
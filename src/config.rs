@@ -0,0 +1,245 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+
+const APP_NAME: &str = "bled-rs";
+const CONFIG_FILE: &str = "config.toml";
+
+/// Editors tend to emit several write events per save; wait this long
+/// after the first one before reloading, to coalesce the burst.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Debug)]
+pub(crate) enum ConfigEvent {
+    Reloaded,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Config {
+    #[serde(default)]
+    pub(crate) presets: HashMap<String, f32>,
+    #[serde(default)]
+    pub(crate) calibration: Calibration,
+    #[serde(default)]
+    pub(crate) reminders: RemindersConfig,
+    #[serde(default)]
+    pub(crate) control: ControlConfig,
+    #[serde(default)]
+    pub(crate) ipc: IpcConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct IpcConfig {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    #[serde(default = "default_socket_path")]
+    pub(crate) socket_path: PathBuf,
+}
+
+fn default_socket_path() -> PathBuf {
+    PathBuf::from("/tmp/bled-rs.sock")
+}
+
+impl Default for IpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            socket_path: default_socket_path(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub(crate) struct ControlConfig {
+    /// BLE + motor lag between issuing `Stop` and the desk actually
+    /// halting, used to predict how far it will coast.
+    #[serde(default = "default_latency_ms")]
+    pub(crate) latency_ms: u64,
+    /// Fixed coast margin added on top of the latency-predicted distance,
+    /// and the dead-band within which a new target is considered "there".
+    #[serde(default = "default_coast_margin_mm")]
+    pub(crate) coast_margin_mm: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RemindersConfig {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    #[serde(default = "default_tolerance_mm")]
+    pub(crate) tolerance_mm: f32,
+    #[serde(default = "default_after_minutes")]
+    pub(crate) after_minutes: u64,
+    /// Preset to move to automatically when the reminder fires. If unset,
+    /// the reminder only notifies (log line and/or webhook).
+    #[serde(default)]
+    pub(crate) auto_preset: Option<String>,
+    /// URL to POST a small JSON payload to when the reminder fires.
+    #[serde(default)]
+    pub(crate) webhook_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub(crate) struct Calibration {
+    #[serde(default = "default_divisor")]
+    pub(crate) divisor: f32,
+    #[serde(default = "default_offset")]
+    pub(crate) offset: f32,
+}
+
+fn default_divisor() -> f32 {
+    43.22
+}
+
+fn default_offset() -> f32 {
+    24.16
+}
+
+fn default_tolerance_mm() -> f32 {
+    10.0
+}
+
+fn default_after_minutes() -> u64 {
+    45
+}
+
+fn default_latency_ms() -> u64 {
+    150
+}
+
+fn default_coast_margin_mm() -> f32 {
+    2.0
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            divisor: default_divisor(),
+            offset: default_offset(),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            presets: HashMap::new(),
+            calibration: Calibration::default(),
+            reminders: RemindersConfig::default(),
+            control: ControlConfig::default(),
+            ipc: IpcConfig::default(),
+        }
+    }
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            latency_ms: default_latency_ms(),
+            coast_margin_mm: default_coast_margin_mm(),
+        }
+    }
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tolerance_mm: default_tolerance_mm(),
+            after_minutes: default_after_minutes(),
+            auto_preset: None,
+            webhook_url: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config from the XDG config dir, falling back to defaults
+    /// (no presets, stock calibration constants) when the file is absent
+    /// or fails to parse.
+    pub(crate) fn load() -> Self {
+        match Self::config_path().and_then(|path| std::fs::read_to_string(path).ok()) {
+            Some(contents) => toml::from_str(&contents).unwrap_or_default(),
+            None => Self::default(),
+        }
+    }
+
+    pub(crate) fn config_path() -> Option<PathBuf> {
+        let dirs = xdg::BaseDirectories::with_prefix(APP_NAME).ok()?;
+        dirs.find_config_file(CONFIG_FILE)
+    }
+
+    /// Resolves user input to a target height: a raw number is used as-is,
+    /// otherwise the input is looked up as a named preset.
+    pub(crate) fn resolve_height(&self, input: &str) -> Option<f32> {
+        if let Ok(value) = input.trim().parse::<f32>() {
+            return Some(value);
+        }
+        self.presets.get(input.trim()).copied()
+    }
+}
+
+/// Watches the config file for writes and pushes reloaded config into
+/// `watch::Receiver` so presets/calibration take effect without a
+/// restart. The background watcher task exits at once if there is no
+/// config file to watch.
+pub(crate) fn spawn_watcher(
+    initial: Config,
+) -> (watch::Receiver<Config>, broadcast::Receiver<ConfigEvent>) {
+    let (config_tx, config_rx) = watch::channel(initial);
+    let (event_tx, event_rx) = broadcast::channel(4);
+
+    tokio::spawn(async move {
+        let Some(path) = Config::config_path() else {
+            return;
+        };
+
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        let Some(file_name) = path.file_name() else {
+            return;
+        };
+
+        let (raw_tx, mut raw_rx) = tokio::sync::mpsc::channel(16);
+        let mut watcher: RecommendedWatcher = match notify::recommended_watcher(move |res| {
+            let _ = raw_tx.blocking_send(res);
+        }) {
+            Ok(watcher) => watcher,
+            Err(_) => return,
+        };
+
+        // Watch the parent directory rather than the file itself: editors
+        // that save atomically (vim's default `backupcopy`, and friends)
+        // replace the file via rename, which invalidates an inotify watch
+        // on the old inode and silently stops delivering events.
+        if watcher.watch(parent, RecursiveMode::NonRecursive).is_err() {
+            return;
+        }
+
+        while let Some(res) = raw_rx.recv().await {
+            let Ok(event) = res else { continue };
+            if !event.paths.iter().any(|p| p.file_name() == Some(file_name)) {
+                continue;
+            }
+
+            // Coalesce the burst of write events a single save can produce.
+            tokio::time::sleep(RELOAD_DEBOUNCE).await;
+            while raw_rx.try_recv().is_ok() {}
+
+            let Ok(contents) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let Ok(reloaded) = toml::from_str::<Config>(&contents) else {
+                continue;
+            };
+
+            let _ = config_tx.send(reloaded);
+            let _ = event_tx.send(ConfigEvent::Reloaded);
+        }
+    });
+
+    (config_rx, event_rx)
+}
@@ -4,9 +4,14 @@ use futures::StreamExt;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
-use tokio::sync::{broadcast, mpsc};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
 use uuid::Uuid;
 
+use crate::config::{Calibration, Config};
+use crate::event;
+
 const ERGOSTOL: Uuid = Uuid::from_u128(0x0000ff12_0000_1000_8000_00805f9b34fb);
 
 const CHAR_WRITE: Uuid = Uuid::from_u128(0x0000ff01_0000_1000_8000_00805f9b34fb);
@@ -27,6 +32,15 @@ pub(crate) enum DeskEvent {
     HeightStatic(f32),
 }
 
+/// The controller's most recently observed position, published so other
+/// tasks (the IPC server seeding a newly-connected client) can read the
+/// desk's actual current state instead of fabricating one.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DeskState {
+    pub(crate) current_height: f32,
+    pub(crate) is_moving: bool,
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum DeskCommand {
     MoveUp,
@@ -46,12 +60,39 @@ impl From<DeskCommand> for &'static [u8] {
     }
 }
 
-fn get_height(p1: u8, p2: u8) -> f32 {
+fn get_height(p1: u8, p2: u8, calibration: &Calibration) -> f32 {
     let raw = ((p1 as u16) << 8) | (p2 as u16);
-    let height = (raw as f32 / 43.22) + 24.16;
+    let height = (raw as f32 / calibration.divisor) + calibration.offset;
     return height;
 }
 
+/// True once `current_height` is already close enough to `target_height`
+/// that the desk shouldn't bother moving at all.
+fn within_dead_band(current_height: f32, target_height: f32, coast_margin: f32) -> bool {
+    (current_height - target_height).abs() <= coast_margin
+}
+
+/// Predicts whether the desk should be stopped now so it settles at
+/// `target_height` instead of coasting past it. `velocity_mm_per_s` is
+/// signed (positive while moving up); `latency` and `coast_margin` are
+/// the tunable BLE/motor-lag and fixed-margin constants from config.
+fn should_stop(
+    moving_up: bool,
+    current_height: f32,
+    target_height: f32,
+    velocity_mm_per_s: f32,
+    latency: Duration,
+    coast_margin: f32,
+) -> bool {
+    let stopping_distance = velocity_mm_per_s.abs() * latency.as_secs_f32() + coast_margin;
+
+    if moving_up {
+        current_height + stopping_distance >= target_height
+    } else {
+        current_height - stopping_distance <= target_height
+    }
+}
+
 pub(crate) async fn device_finder(
     central: btleplug::platform::Adapter,
 ) -> Result<btleplug::platform::Peripheral> {
@@ -73,7 +114,9 @@ pub(crate) async fn device_finder(
 
 pub(crate) async fn start_read_thread(
     desk: Arc<btleplug::platform::Peripheral>,
-    notify: broadcast::Sender<DeskEvent>,
+    notify: event::Writer<DeskEvent>,
+    config: watch::Receiver<Config>,
+    mut shutdown: event::Reader<()>,
 ) -> Result<()> {
     let notify_char = desk
         .characteristics()
@@ -86,46 +129,56 @@ pub(crate) async fn start_read_thread(
     desk.subscribe(&notify_char).await?;
     let mut stream = desk.notifications().await?;
 
-    while let Some(data) = stream.next().await {
+    loop {
+        let data = tokio::select! {
+            _ = shutdown.recv() => break,
+            data = stream.next() => match data {
+                Some(data) => data,
+                None => break,
+            },
+        };
+
         let (p1, p2, p3, p4) = (data.value[0], data.value[1], data.value[2], data.value[3]);
 
         match p1 {
             0x0B => {
-                let _ = notify.send(DeskEvent::StartMoving);
+                notify.send(DeskEvent::StartMoving);
             }
             0x08 => {
-                let h = get_height(p3, p4);
+                let h = get_height(p3, p4, &config.borrow().calibration);
 
                 match p2 {
                     0x01 => {
-                        let _ = notify.send(DeskEvent::HeightMoving(h));
+                        notify.send(DeskEvent::HeightMoving(h));
                     }
                     0x06 => {
-                        let _ = notify.send(DeskEvent::HeightStatic(h));
+                        notify.send(DeskEvent::HeightStatic(h));
                     }
                     _ => {}
                 };
             }
             0x09 => {
-                let h = get_height(p3, p4);
-                let _ = notify.send(DeskEvent::MovingEnd(h));
+                let h = get_height(p3, p4, &config.borrow().calibration);
+                notify.send(DeskEvent::MovingEnd(h));
             }
             0x02 => {
-                let _ = notify.send(DeskEvent::StartMovingUp);
+                notify.send(DeskEvent::StartMovingUp);
             }
             0x01 => {
-                let _ = notify.send(DeskEvent::StartMovingDown);
+                notify.send(DeskEvent::StartMovingDown);
             }
             _ => {}
         }
     }
 
+    desk.unsubscribe(&notify_char).await?;
     Ok(())
 }
 
 pub(crate) async fn start_write_thread(
     desk: Arc<btleplug::platform::Peripheral>,
     mut cmd: mpsc::Receiver<DeskCommand>,
+    mut shutdown: event::Reader<()>,
 ) -> Result<()> {
     let notify_char = &desk
         .characteristics()
@@ -135,22 +188,34 @@ pub(crate) async fn start_write_thread(
         .unwrap()
         .clone();
 
-    while let Some(cmd) = cmd.recv().await {
+    loop {
+        let cmd = tokio::select! {
+            _ = shutdown.recv() => break,
+            cmd = cmd.recv() => match cmd {
+                Some(cmd) => cmd,
+                None => break,
+            },
+        };
+
         desk.write(notify_char, cmd.into(), WithoutResponse).await?;
     }
     Ok(())
 }
 
 pub(crate) async fn start_controller(
-    mut desk_events: broadcast::Receiver<DeskEvent>,
+    mut desk_events: event::Reader<DeskEvent>,
     desk_commands: mpsc::Sender<DeskCommand>,
     mut get_target_height: mpsc::Receiver<f32>,
+    config: watch::Receiver<Config>,
+    mut shutdown: event::Reader<()>,
+    state: watch::Sender<DeskState>,
 ) -> Result<()> {
     desk_commands.send(DeskCommand::GetHeight).await?;
 
     let mut current_height: f32 = 0.0;
     let mut target_height: f32 = 0.0;
     let mut move_up: Option<bool> = None;
+    let mut last_moving: Option<(f32, Instant)> = None;
     let is_moving = Arc::new(AtomicBool::new(false));
 
     loop {
@@ -159,6 +224,8 @@ pub(crate) async fn start_controller(
         }
 
         tokio::select! {
+            _ = shutdown.recv() => break,
+
             Some(target) = get_target_height.recv() => {
 
                 if is_moving.load(Ordering::SeqCst) {
@@ -166,16 +233,21 @@ pub(crate) async fn start_controller(
                 }
 
                 target_height = target;
-                if current_height <= target_height {
+                last_moving = None;
+                let coast_margin = config.borrow().control.coast_margin_mm;
+
+                if within_dead_band(current_height, target_height, coast_margin) {
+                    move_up = None;
+                } else if current_height < target_height {
                     move_up = Some(true);
                     desk_commands.send(DeskCommand::MoveUp).await?;
-                } else if current_height >= target_height {
+                } else {
                     move_up = Some(false);
                     desk_commands.send(DeskCommand::MoveDown).await?;
                 }
             }
 
-            Ok(event) = desk_events.recv() => {
+            Some(event) = desk_events.recv() => {
                 match event {
                     DeskEvent::StartMoving => {
                         let is_moving = is_moving.clone();
@@ -183,22 +255,34 @@ pub(crate) async fn start_controller(
                     }
 
                     DeskEvent::HeightMoving(h) => {
+                        let now = Instant::now();
+                        let velocity = match last_moving {
+                            Some((prev_h, prev_t)) => {
+                                let dt = now.duration_since(prev_t).as_secs_f32();
+                                if dt > 0.0 { (h - prev_h) / dt } else { 0.0 }
+                            }
+                            None => 0.0,
+                        };
+                        last_moving = Some((h, now));
+
                         current_height = h;
                         is_moving.store(true, Ordering::SeqCst);
 
-                        match move_up {
-                            Some(true) => {
-                                if current_height > target_height {
-                                    desk_commands.send(DeskCommand::Stop).await?;
-                                };
-                            },
-                            Some(false) => {
-                                if current_height < target_height {
-                                    desk_commands.send(DeskCommand::Stop).await?;
-                                };
-                            },
-                            _ => {},
-                        };
+                        if let Some(moving_up) = move_up {
+                            let control = config.borrow().control;
+                            let latency = Duration::from_millis(control.latency_ms);
+
+                            if should_stop(
+                                moving_up,
+                                current_height,
+                                target_height,
+                                velocity,
+                                latency,
+                                control.coast_margin_mm,
+                            ) {
+                                desk_commands.send(DeskCommand::Stop).await?;
+                            }
+                        }
                     }
 
                     DeskEvent::HeightStatic(h) => {
@@ -218,10 +302,77 @@ pub(crate) async fn start_controller(
                         current_height = h;
                         is_moving.store(false, Ordering::SeqCst);
                         move_up = None;
+                        last_moving = None;
                         target_height = 0.0;
                     }
                 }
+
+                let _ = state.send(DeskState {
+                    current_height,
+                    is_moving: is_moving.load(Ordering::SeqCst),
+                });
             }
         }
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_band_skips_moving_when_already_close() {
+        assert!(within_dead_band(100.0, 101.0, 2.0));
+        assert!(!within_dead_band(100.0, 105.0, 2.0));
+    }
+
+    #[test]
+    fn predictive_stop_fires_before_crossing_target_moving_up() {
+        // 50 mm/s approach, 150ms latency -> 7.5mm stopping distance, plus 2mm margin.
+        let latency = Duration::from_millis(150);
+        let coast_margin = 2.0;
+        let velocity = 50.0;
+
+        // Still far from target: must not stop yet.
+        assert!(!should_stop(true, 90.0, 110.0, velocity, latency, coast_margin));
+
+        // Within the predicted stopping distance of the target: stop now,
+        // before `current_height` has actually reached `target_height`.
+        assert!(should_stop(true, 100.5, 110.0, velocity, latency, coast_margin));
+    }
+
+    #[test]
+    fn predictive_stop_fires_before_crossing_target_moving_down() {
+        let latency = Duration::from_millis(150);
+        let coast_margin = 2.0;
+        let velocity = -40.0;
+
+        assert!(!should_stop(false, 120.0, 100.0, velocity, latency, coast_margin));
+        assert!(should_stop(false, 107.0, 100.0, velocity, latency, coast_margin));
+    }
+
+    #[test]
+    fn synthetic_height_sequence_stops_before_overshoot() {
+        let latency = Duration::from_millis(150);
+        let coast_margin = 2.0;
+        let target = 110.0;
+
+        // Synthetic HeightMoving(h) readings closing in on the target while
+        // moving up at a steady 50 mm/s.
+        let readings = [90.0, 100.0, 105.0, 108.0, 109.0];
+        let mut stopped_at = None;
+
+        for h in readings {
+            let velocity = 50.0;
+            if should_stop(true, h, target, velocity, latency, coast_margin) {
+                stopped_at = Some(h);
+                break;
+            }
+        }
+
+        let stopped_at = stopped_at.expect("controller should have stopped before reaching target");
+        assert!(stopped_at < target, "stop must fire before the raw crossing");
+    }
 }
@@ -0,0 +1,47 @@
+use tokio::sync::broadcast;
+
+/// Thin, cloneable wrapper over a broadcast sender. `send` swallows
+/// delivery errors on purpose: once every receiver task has shut down
+/// there is nobody left to deliver to, and that's an expected part of
+/// teardown rather than something worth propagating or unwrapping.
+#[derive(Clone)]
+pub(crate) struct Writer<T> {
+    inner: broadcast::Sender<T>,
+}
+
+impl<T: Clone> Writer<T> {
+    pub(crate) fn send(&self, value: T) {
+        let _ = self.inner.send(value);
+    }
+
+    pub(crate) fn subscribe(&self) -> Reader<T> {
+        Reader {
+            inner: self.inner.subscribe(),
+        }
+    }
+}
+
+pub(crate) struct Reader<T> {
+    inner: broadcast::Receiver<T>,
+}
+
+impl<T: Clone> Reader<T> {
+    /// Resolves to `None` only once the sender side is actually gone.
+    /// A `Lagged` error just means this receiver fell behind a full
+    /// channel and missed some values — not that the channel closed —
+    /// so it resyncs and keeps waiting instead of returning `None`.
+    pub(crate) async fn recv(&mut self) -> Option<T> {
+        loop {
+            match self.inner.recv().await {
+                Ok(value) => return Some(value),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+pub(crate) fn channel<T: Clone>(capacity: usize) -> (Writer<T>, Reader<T>) {
+    let (inner_tx, inner_rx) = broadcast::channel(capacity);
+    (Writer { inner: inner_tx }, Reader { inner: inner_rx })
+}
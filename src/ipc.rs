@@ -0,0 +1,140 @@
+use anyhow::Result;
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, watch};
+
+use crate::config::Config;
+use crate::desk_controller::{DeskCommand, DeskEvent, DeskState};
+use crate::event;
+
+/// A single newline-delimited JSON command. Exactly one field is expected
+/// to be set per line, e.g. `{"set_height": 95.0}` or `{"preset": "stand"}`.
+#[derive(Debug, Deserialize)]
+struct IpcCommand {
+    #[serde(default)]
+    set_height: Option<f32>,
+    #[serde(default)]
+    preset: Option<String>,
+    #[serde(default)]
+    stop: bool,
+    #[serde(default)]
+    get_state: bool,
+}
+
+/// Listens on the configured Unix socket for newline-delimited JSON
+/// commands (`set_height`, `preset`, `stop`, `get_state`) and streams
+/// `DeskEvent`-derived state updates back to each connected client. Lets
+/// external tools (Stream Deck, a physical macro pad) drive the desk
+/// without embedding device-specific HID handling in this crate.
+pub(crate) async fn serve(
+    config: watch::Receiver<Config>,
+    set_target_height: mpsc::Sender<f32>,
+    desk_commands: mpsc::Sender<DeskCommand>,
+    desk_events: event::Writer<DeskEvent>,
+    desk_state: watch::Receiver<DeskState>,
+) -> Result<()> {
+    if !config.borrow().ipc.enabled {
+        return Ok(());
+    }
+
+    let socket_path = config.borrow().ipc.socket_path.clone();
+    let _ = std::fs::remove_file(&socket_path);
+    let listener = UnixListener::bind(&socket_path)?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+
+        let config = config.clone();
+        let set_target_height = set_target_height.clone();
+        let desk_commands = desk_commands.clone();
+        let desk_events = desk_events.subscribe();
+        let desk_state = desk_state.clone();
+
+        tokio::spawn(async move {
+            let _ = handle_client(
+                stream,
+                config,
+                set_target_height,
+                desk_commands,
+                desk_events,
+                desk_state,
+            )
+            .await;
+        });
+    }
+}
+
+async fn handle_client(
+    stream: UnixStream,
+    config: watch::Receiver<Config>,
+    set_target_height: mpsc::Sender<f32>,
+    desk_commands: mpsc::Sender<DeskCommand>,
+    mut desk_events: event::Reader<DeskEvent>,
+    desk_state: watch::Receiver<DeskState>,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    // Seed from the controller's actual last-known state rather than
+    // fabricating `0.0`/not-moving for a client that queries immediately
+    // after connecting, before any fresh `DeskEvent` has arrived.
+    let seed = *desk_state.borrow();
+    let mut current_height: f32 = seed.current_height;
+    let mut is_moving = seed.is_moving;
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else { break };
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let Ok(command) = serde_json::from_str::<IpcCommand>(&line) else {
+                    continue;
+                };
+
+                if let Some(height) = command.set_height {
+                    let _ = set_target_height.send(height).await;
+                } else if let Some(preset) = command.preset {
+                    if let Some(height) = config.borrow().presets.get(&preset).copied() {
+                        let _ = set_target_height.send(height).await;
+                    }
+                } else if command.stop {
+                    let _ = desk_commands.send(DeskCommand::Stop).await;
+                } else if command.get_state {
+                    let state = serde_json::json!({
+                        "current_height": current_height,
+                        "is_moving": is_moving,
+                    });
+                    writer.write_all(format!("{state}\n").as_bytes()).await?;
+                }
+            }
+
+            Some(event) = desk_events.recv() => {
+                match event {
+                    DeskEvent::HeightMoving(h) => {
+                        current_height = h;
+                        is_moving = true;
+                    }
+                    DeskEvent::HeightStatic(h) | DeskEvent::MovingEnd(h) => {
+                        current_height = h;
+                        is_moving = false;
+                    }
+                    DeskEvent::StartMoving | DeskEvent::StartMovingUp | DeskEvent::StartMovingDown => {
+                        is_moving = true;
+                    }
+                }
+
+                let update = serde_json::json!({
+                    "current_height": current_height,
+                    "is_moving": is_moving,
+                });
+                writer.write_all(format!("{update}\n").as_bytes()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,17 +1,34 @@
 pub(crate) mod cli;
+pub(crate) mod config;
 pub(crate) mod desk_controller;
+pub(crate) mod event;
+pub(crate) mod ipc;
+pub(crate) mod reminders;
 
 use anyhow::Result;
+use futures::StreamExt;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook_tokio::Signals;
 use std::sync::Arc;
+use std::time::Duration;
 
 use btleplug::api::{Manager as _, Peripheral};
 use btleplug::platform::Manager;
 
 use cli::{interactive_mode, logger};
-use desk_controller::{device_finder, start_controller, start_read_thread, start_write_thread};
+use config::Config;
+use desk_controller::{
+    device_finder, start_controller, start_read_thread, start_write_thread, DeskCommand,
+    DeskEvent, DeskState,
+};
+use reminders::reminder_task;
+
+use ipc::serve as serve_ipc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let (config, config_events) = config::spawn_watcher(Config::load());
+
     let manager = Manager::new().await?;
     let adapters = manager.adapters().await?;
     let central = adapters
@@ -26,24 +43,90 @@ async fn main() -> Result<()> {
     desk.connect().await?;
     desk.discover_services().await?;
 
-    let (s, desk_events) = tokio::sync::broadcast::channel(1);
+    // Capacity 1 was fine with a single subscriber; by now the controller,
+    // logger, reminders, shutdown handler, and one subscriber per IPC
+    // client all race for a slot, so a small burst of `HeightMoving`
+    // events would starve a slow subscriber into spurious lag.
+    let (s, desk_events) = event::channel::<DeskEvent>(16);
     let (desk_commands, r) = tokio::sync::mpsc::channel(1);
     let (set_target_height, get_target_height) = tokio::sync::mpsc::channel(1);
+    let (shutdown_writer, _) = event::channel::<()>(1);
+    let (desk_state, ipc_desk_state) = tokio::sync::watch::channel(DeskState::default());
+
+    let reminder_events = s.subscribe();
+    let reminder_target_height = set_target_height.clone();
+    let reminder_config = config.clone();
+    tokio::spawn(async move {
+        let _ = reminder_task(reminder_events, reminder_target_height, reminder_config).await;
+    });
+
+    let ipc_config = config.clone();
+    let ipc_target_height = set_target_height.clone();
+    let ipc_desk_commands = desk_commands.clone();
+    let ipc_events = s.clone();
+    tokio::spawn(async move {
+        let _ = serve_ipc(
+            ipc_config,
+            ipc_target_height,
+            ipc_desk_commands,
+            ipc_events,
+            ipc_desk_state,
+        )
+        .await;
+    });
 
+    let cli_config = config.clone();
     tokio::spawn(async move {
-        let _ = interactive_mode(set_target_height).await;
+        let _ = interactive_mode(set_target_height, cli_config).await;
     });
 
-    let ui = desk_events.resubscribe();
+    let ui = s.subscribe();
     tokio::spawn(async move {
-        let _ = logger(ui).await;
+        let _ = logger(ui, config_events).await;
+    });
+
+    let shutdown_signal_writer = shutdown_writer.clone();
+    let mut shutdown_signal_events = s.subscribe();
+    let shutdown_desk_commands = desk_commands.clone();
+    tokio::spawn(async move {
+        let Ok(mut signals) = Signals::new([SIGINT, SIGTERM]) else {
+            return;
+        };
+
+        signals.next().await;
+
+        let _ = shutdown_desk_commands.send(DeskCommand::Stop).await;
+
+        // If the desk was already idle, `Stop` produces no new BLE
+        // notification and this would wait forever. Give it a short
+        // window to confirm a real stop, then shut down regardless.
+        let _ = tokio::time::timeout(Duration::from_millis(500), async {
+            loop {
+                match shutdown_signal_events.recv().await {
+                    Some(DeskEvent::HeightStatic(_)) | Some(DeskEvent::MovingEnd(_)) | None => break,
+                    Some(_) => continue,
+                }
+            }
+        })
+        .await;
+
+        shutdown_signal_writer.send(());
     });
 
     let _ = tokio::join!(
-        start_read_thread(desk.clone(), s),
-        start_write_thread(desk.clone(), r),
-        start_controller(desk_events, desk_commands, get_target_height),
+        start_read_thread(desk.clone(), s, config.clone(), shutdown_writer.subscribe()),
+        start_write_thread(desk.clone(), r, shutdown_writer.subscribe()),
+        start_controller(
+            desk_events,
+            desk_commands,
+            get_target_height,
+            config,
+            shutdown_writer.subscribe(),
+            desk_state,
+        ),
     );
 
+    desk.disconnect().await?;
+
     Ok(())
 }
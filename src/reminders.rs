@@ -0,0 +1,86 @@
+use anyhow::Result;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+
+use crate::config::Config;
+use crate::desk_controller::DeskEvent;
+use crate::event;
+
+/// How often we check whether the sit/stand reminder should fire. Fine
+/// grained enough that `after_minutes` feels accurate without busy-looping.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Caps how long a webhook POST can take. Without this, a slow or dead
+/// user-supplied `webhook_url` would hang the single `select!` loop below
+/// indefinitely, stalling auto-preset sends and real position-change
+/// timer resets along with it.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tracks how long the desk has stayed within `tolerance_mm` of its last
+/// position and, once `after_minutes` elapses, either auto-sends a preset
+/// height or fires a webhook notification (or both). The timer resets
+/// whenever `DeskEvent::MovingEnd`/`HeightStatic` reports a real position
+/// change.
+pub(crate) async fn reminder_task(
+    mut desk_events: event::Reader<DeskEvent>,
+    set_target_height: mpsc::Sender<f32>,
+    config: watch::Receiver<Config>,
+) -> Result<()> {
+    let mut last_height: Option<f32> = None;
+    let mut since_change = Instant::now();
+    let mut ticker = tokio::time::interval(POLL_INTERVAL);
+    let webhook_client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let reminders = config.borrow().reminders.clone();
+                if !reminders.enabled {
+                    continue;
+                }
+
+                let due = Duration::from_secs(reminders.after_minutes * 60);
+                if since_change.elapsed() < due {
+                    continue;
+                }
+
+                println!("Time to change position");
+
+                if let Some(preset) = &reminders.auto_preset {
+                    if let Some(height) = config.borrow().presets.get(preset).copied() {
+                        let _ = set_target_height.send(height).await;
+                    }
+                }
+
+                if let Some(url) = &reminders.webhook_url {
+                    notify_webhook(&webhook_client, url).await;
+                }
+
+                since_change = Instant::now();
+            }
+
+            Some(event) = desk_events.recv() => {
+                if let DeskEvent::MovingEnd(height) | DeskEvent::HeightStatic(height) = event {
+                    let tolerance = config.borrow().reminders.tolerance_mm;
+                    let moved = last_height
+                        .map(|prev| (prev - height).abs() > tolerance)
+                        .unwrap_or(true);
+
+                    last_height = Some(height);
+                    if moved {
+                        since_change = Instant::now();
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn notify_webhook(client: &reqwest::Client, url: &str) {
+    let payload = serde_json::json!({ "content": "Time to change position" });
+    let _ = client.post(url).json(&payload).send().await;
+}